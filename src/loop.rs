@@ -5,32 +5,87 @@
 //! uses its native event loop mechanism:
 //!
 //! - **Windows**: Win32 message loop with PeekMessage for non-blocking processing
-//! - **macOS**: NSApplication with NSRunLoop and NSTimer for periodic polling
-//! - **iOS**: NSRunLoop with NSTimer for periodic polling (without NSApplication)
+//! - **macOS**: NSApplication with NSRunLoop and a `CFRunLoopSource` for prompt polling
+//! - **iOS**: NSRunLoop with a `CFRunLoopSource` for prompt polling (without NSApplication)
 //! - **Android**: JNI integration with Java MainLoop for Android event system
 //!
 //! The module exports platform-appropriate `run` functions that initialize the
 //! Compo runtime and integrate it with the platform's native event loop.
+//!
+//! # Waking the loop
+//!
+//! Polling the runtime on every tick of a fixed-interval timer wastes CPU while
+//! idle and adds up to a full tick of latency once a future actually becomes
+//! ready (for example one woken from a worker thread through [`vm_exec`] on
+//! Android). To avoid that, the native backends are driven by a [`Waker`] that
+//! prompts the loop to poll immediately, borrowing the run-loop-source
+//! technique from Chromium's `message_pump_mac`: on macOS/iOS a version-0
+//! `CFRunLoopSource` is added to the main run loop and signalled; on Windows a
+//! custom `WM_APP` message is posted to the main thread; on Android a
+//! `Runnable` is posted to the main `Handler`. A component can obtain the
+//! [`Waker`] for the loop it is running on via [`current_waker`] and hand a
+//! clone to a worker thread, which calls [`Waker::wake`] once it has
+//! completed the future the loop was waiting on.
+//!
+//! # Delayed work
+//!
+//! Timer-based APIs (`sleep`, `interval`, ...) need the loop to wake up again
+//! at a specific instant rather than immediately. Each call to the bound poll
+//! function (see [`Pump::bind`]) returns the runtime's earliest pending
+//! deadline, and the backend re-arms a single timer at exactly that instant
+//! instead of firing on a fixed interval: macOS/iOS move a reused `NSTimer`'s
+//! fire date with `setFireDate:`, and Windows folds the deadline into the
+//! timeout passed to `MsgWaitForMultipleObjectsEx`. Firing early is harmless -
+//! the loop just polls and re-arms - so the deadline is always recomputed
+//! after polling, which keeps newly scheduled or cancelled timers correctly
+//! reflected.
+//!
+//! # Pluggable pumps
+//!
+//! The native loop integration for each platform is a [`Pump`]: a small,
+//! swappable object that knows how to wait for and dispatch that platform's
+//! native events. [`run_with`] drives any `Pump` the same way [`run`] drives
+//! the built-in [`Win32Pump`], [`AppKitPump`]/[`RunLoopPump`] and
+//! [`AndroidJniPump`], which makes it possible to embed Compo inside an
+//! already-running loop (a `winit` app, a `tokio` runtime, a headless test
+//! harness) instead of Compo owning `main()`. [`ManualPump`] is a trivial
+//! pump with no native loop at all, intended for deterministically stepping
+//! the runtime from tests.
+//!
+//! # Graceful shutdown
+//!
+//! [`current_loop_handle`] returns a clonable [`LoopHandle`] from within a
+//! running component; calling [`LoopHandle::quit`] asks the loop to stop,
+//! doing the right native thing per platform (`PostThreadMessageW` with
+//! `WM_QUIT` on Windows, `CFRunLoopStop`/`-[NSApplication stop:]` on
+//! macOS/iOS, a Java `MainLoop.finish()` call on Android) and waking it
+//! immediately if it is currently idle, instead of relying on the OS to kill
+//! the process.
 
 use compo::prelude::*;
 #[cfg(windows)]
-use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, MSG, PM_REMOVE, PeekMessageW, TranslateMessage, WM_QUIT,
+use windows::Win32::{
+    Foundation::HWND,
+    System::Threading::{GetCurrentThreadId, INFINITE},
+    UI::WindowsAndMessaging::{
+        DispatchMessageW, MSG, MWMO_INPUTAVAILABLE, MsgWaitForMultipleObjectsEx, PM_REMOVE,
+        PeekMessageW, PostThreadMessageW, QS_ALLINPUT, TranslateMessage, WM_APP, WM_QUIT,
+    },
 };
 #[cfg(target_os = "ios")]
 use {
     block2::RcBlock,
     objc2::{ClassType, msg_send},
-    objc2_foundation::{NSRunLoop, NSString, NSTimer},
+    objc2_foundation::{NSDate, NSRunLoop, NSString, NSTimer},
 };
 #[cfg(target_os = "android")]
 use {
-    jni::{AttachGuard, JNIEnv, JavaVM, NativeMethod, errors::Result as JniResult},
-    std::{
-        any::Any,
-        cell::{Cell, RefCell},
-        ptr::null_mut,
+    jni::{
+        AttachGuard, JNIEnv, JavaVM, NativeMethod,
+        objects::{GlobalRef, JValue},
+        sys::jlong,
     },
+    std::{any::Any, sync::OnceLock},
     tracing::error,
 };
 
@@ -38,68 +93,1054 @@ use {
 use {
     block2::RcBlock,
     objc2::{ClassType, msg_send},
-    objc2_foundation::{NSRunLoop, NSString, NSTimer},
+    objc2_foundation::{NSDate, NSRunLoop, NSString, NSTimer},
 };
 
-// Thread-local storage for Android runtime and component management.
+use std::{
+    cell::{Cell, RefCell},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+/// The function a [`Pump`] calls to advance the runtime: it polls every ready
+/// task and returns the runtime's next scheduled deadline, if any.
+type PollFn = Box<dyn FnMut() -> Option<Instant>>;
+
+// Per-activity Android runtime state.
 //
-// On Android, we use thread-local storage to maintain the Compo runtime and
-// component instances. This is necessary because Android's JNI callbacks
-// need access to the runtime from the same thread where it was created.
+// Earlier revisions kept the runtime, component and pump in thread-local
+// statics, which made it impossible for more than one Compo activity to run
+// in the same process (or for callbacks to reach the runtime from a thread
+// other than the one that called `run`). Instead, each `run` call boxes its
+// state and hands Java back an opaque pointer (as a `jlong`) that is passed
+// into every subsequent native call (`poll_all`, the lifecycle natives), so
+// the runtime is resolved from the caller rather than from thread-local
+// storage. The `JavaVM` is the one piece of state that is legitimately
+// process-wide, so it is still cached globally, just not per-thread.
+#[cfg(target_os = "android")]
+struct AndroidRuntimeState {
+    rt: Rc<Runtime<'static, ()>>,
+    component: Rc<dyn Any>,
+    pump: AndroidJniPump,
+    listeners: Vec<Box<dyn FnMut(LifecycleEvent)>>,
+}
+
+/// The process-wide `JavaVM`, cached once on the first call to `run`.
+///
+/// Unlike the runtime and component, the VM handle is valid from any thread
+/// and shared by every activity in the process, so a single global is
+/// appropriate here.
+#[cfg(target_os = "android")]
+static JAVA_VM: OnceLock<JavaVM> = OnceLock::new();
+
 #[cfg(target_os = "android")]
 thread_local! {
-    /// The Compo runtime instance for processing async tasks
-    static RT: Rc<Runtime<'static, ()>> = Rc::new(Runtime::new());
-    /// Storage for the root component instance
-    static COMPONENT: Cell<Rc<dyn Any>> = Cell::new(Rc::new(()));
-    /// Storage for the JavaVM instance to allow JNI calls from any thread
-    ///
-    /// This is used to store the JavaVM instance obtained during JNI_OnLoad
-    /// so that we can attach threads to the JVM when needed for callbacks.
-    /// The RefCell allows mutable access to the JavaVM instance when attaching
-    /// new threads or performing JNI operations.
-    static JAVA_VM: RefCell<JniResult<JavaVM>> = RefCell::new(unsafe { JavaVM::from_raw(null_mut()) });
+    /// The runtime state handle most recently activated on this thread, used
+    /// so [`on_lifecycle_event`] can reach the right activity's listener list
+    /// without every component needing to thread a handle through.
+    static CURRENT_HANDLE: Cell<jlong> = const { Cell::new(0) };
+}
+
+#[cfg(target_os = "android")]
+unsafe fn state_from_handle<'a>(handle: jlong) -> &'a mut AndroidRuntimeState {
+    unsafe { &mut *(handle as *mut AndroidRuntimeState) }
+}
+
+/// An Android activity lifecycle event, mirroring the `Activity`/`Application`
+/// callbacks (`onResume`, `onPause`, ...) that the winit Android backend also
+/// forwards, plus the process-wide low-memory warning.
+#[cfg(target_os = "android")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// The activity hosting the Compo runtime was created.
+    Create,
+    /// The activity came to the foreground and is receiving input.
+    Resume,
+    /// The activity is no longer in the foreground but is still visible.
+    Pause,
+    /// The activity is no longer visible.
+    Stop,
+    /// The activity is being destroyed; after this, the runtime is torn down.
+    Destroy,
+    /// The system is low on memory; components should release caches.
+    LowMemory,
+    /// The activity is about to be destroyed but may be recreated; components
+    /// that hold transient UI state should persist it now.
+    SaveInstanceState,
+}
+
+/// Subscribes `listener` to [`LifecycleEvent`]s delivered from the
+/// `rust/compo/MainLoop` lifecycle natives registered by `run`.
+///
+/// Listeners run on the thread that owns the runtime, in the order they were
+/// registered, and are never automatically removed - this is intended to be
+/// called once per long-lived component. Must be called from the thread that
+/// is currently running a Compo activity (i.e. from within `entry` or a task
+/// it spawned); otherwise there is no runtime state to attach the listener to
+/// and the call is a no-op.
+#[cfg(target_os = "android")]
+pub fn on_lifecycle_event(listener: impl FnMut(LifecycleEvent) + 'static) {
+    let handle = CURRENT_HANDLE.get();
+    if handle == 0 {
+        error!("on_lifecycle_event called with no active Compo runtime on this thread.");
+        return;
+    }
+    unsafe { state_from_handle(handle) }
+        .listeners
+        .push(Box::new(listener));
+}
+
+/// Delivers `event` to every listener registered with [`on_lifecycle_event`]
+/// on `state`'s activity.
+#[cfg(target_os = "android")]
+fn dispatch_lifecycle_event(state: &mut AndroidRuntimeState, event: LifecycleEvent) {
+    for listener in state.listeners.iter_mut() {
+        listener(event);
+    }
 }
 
-/// Handles Windows message processing for the event loop.
+/// A pluggable source of native event-loop integration.
 ///
-/// This function processes Windows messages using PeekMessage instead of GetMessage
-/// to avoid blocking the event loop. It handles WM_QUIT messages for graceful shutdown
-/// and dispatches other messages to their appropriate window procedures.
+/// Implementing `Pump` lets Compo be embedded inside an already-running event
+/// loop instead of Compo owning `main()`: [`run_with`] takes any `Pump` and
+/// drives it the same way [`run`] drives the platform default.
+pub trait Pump {
+    /// Binds the function used to advance the runtime. Called exactly once,
+    /// before the pump is driven, so implementations can wire it into their
+    /// native wakeup mechanism (a `CFRunLoopSource`'s perform callback, a
+    /// Windows message handler, an Android `Runnable`, ...).
+    fn bind(&mut self, poll: PollFn);
+
+    /// Waits until there is native work to process, the next deadline
+    /// elapses, or [`Pump::wake`] is called from another thread.
+    /// Implementations that cannot block (e.g. [`ManualPump`]) may return
+    /// immediately.
+    fn wait_for_work(&mut self);
+
+    /// Drains and dispatches any pending native events (Win32 messages,
+    /// AppKit/UIKit run loop turns, Android JNI callbacks, ...), polling the
+    /// runtime as appropriate.
+    fn process_native_events(&mut self);
+
+    /// Wakes this pump's native loop so the next [`Pump::wait_for_work`]
+    /// call returns promptly; the built-in pumps do the same as
+    /// [`Waker::wake`] on their [`Pump::waker`]. To wake the loop from
+    /// another thread, hand that thread the [`Waker`] instead.
+    fn wake(&self);
+
+    /// Reports whether [`run_with`] should stop driving this pump. The
+    /// default never requests a stop; [`Win32Pump`], [`RunLoopPump`],
+    /// [`AppKitPump`] and [`ManualPump`] override this once their
+    /// [`LoopHandle`] has been asked to `quit()`. [`AndroidJniPump`] keeps
+    /// the default: its `quit()` asks Java to finish the activity instead.
+    fn should_quit(&self) -> bool {
+        false
+    }
+
+    /// Returns a clonable [`LoopHandle`] that can ask this pump's loop to
+    /// stop from any thread, waking it immediately if it is idle.
+    fn handle(&self) -> LoopHandle;
+
+    /// Returns a clonable [`Waker`] that can interrupt a concurrent or
+    /// future [`Pump::wait_for_work`] call from any thread, e.g. to have a
+    /// future woken from a worker thread picked up promptly.
+    fn waker(&self) -> Waker;
+}
+
+/// Creates the runtime and root component, spawns `entry`, and drives `pump`
+/// until it reports [`Pump::should_quit`].
 ///
-/// # Arguments
-/// * `r#loop` - Reference to the Loop instance for controlling the event loop
+/// This is the generic entry point behind [`run`]: the platform `run`
+/// functions simply construct their default [`Pump`] and call `run_with`,
+/// and integrators can do the same with a custom `Pump` to embed Compo inside
+/// their own event loop.
+///
+/// # Type Parameters
+/// * `C` - The component type that implements `Component<'a>`
+/// * `F` - The async function type that takes a `Weak<C>` and returns a future
+/// * `P` - The pump driving the native event loop
+pub fn run_with<'a, C, F, P>(mut pump: P, entry: F)
+where
+    C: Component<'a> + 'a,
+    F: AsyncFn(Weak<C>) + 'a,
+    P: Pump,
+{
+    let rt = Rc::new(Runtime::new());
+    let rt_weak = Rc::downgrade(&rt);
+    let c = Rc::new(C::new(rt_weak.clone()));
+    let c_weak = Rc::downgrade(&c);
+    rt.spawn(async move { entry(c_weak).await });
+
+    // Set before `bind`, not after: `Win32Pump`/`RunLoopPump::bind` poll the
+    // runtime synchronously (on this thread) to seed their initial deadline,
+    // so the component's first poll must already see these populated.
+    CURRENT_LOOP_HANDLE.set(Some(pump.handle()));
+    CURRENT_WAKER.set(Some(pump.waker()));
+
+    pump.bind(Box::new(move || {
+        let rt = rt_weak.upgrade()?;
+        rt.poll_all();
+        rt.next_deadline()
+    }));
+
+    while !pump.should_quit() {
+        pump.wait_for_work();
+        pump.process_native_events();
+    }
+    CURRENT_WAKER.set(None);
+    CURRENT_LOOP_HANDLE.set(None);
+}
+
+/// Minimal raw bindings onto the CoreFoundation run loop APIs used to build a
+/// wakeable `CFRunLoopSource`.
+///
+/// Only the handful of entry points required by [`RunLoopSource`] and
+/// [`RunLoopPump`] are
+/// declared here; the rest of CoreFoundation is out of scope for this crate.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod cf {
+    use core::ffi::c_void;
+
+    pub type CFRunLoopRef = *mut c_void;
+    pub type CFRunLoopSourceRef = *mut c_void;
+    pub type CFStringRef = *const c_void;
+    pub type CFIndex = isize;
+
+    #[repr(C)]
+    pub struct CFRunLoopSourceContext {
+        pub version: CFIndex,
+        pub info: *mut c_void,
+        pub retain: Option<unsafe extern "C" fn(*const c_void) -> *const c_void>,
+        pub release: Option<unsafe extern "C" fn(*const c_void)>,
+        pub copy_description: Option<unsafe extern "C" fn(*const c_void) -> CFStringRef>,
+        pub equal: Option<unsafe extern "C" fn(*const c_void, *const c_void) -> u8>,
+        pub hash: Option<unsafe extern "C" fn(*const c_void) -> usize>,
+        pub schedule: Option<unsafe extern "C" fn(*mut c_void, CFRunLoopRef, CFStringRef)>,
+        pub cancel: Option<unsafe extern "C" fn(*mut c_void, CFRunLoopRef, CFStringRef)>,
+        pub perform: Option<unsafe extern "C" fn(*mut c_void)>,
+    }
+
+    unsafe extern "C" {
+        pub static kCFRunLoopDefaultMode: CFStringRef;
+
+        pub fn CFRunLoopGetMain() -> CFRunLoopRef;
+        pub fn CFRunLoopSourceCreate(
+            allocator: *const c_void,
+            order: CFIndex,
+            context: *mut CFRunLoopSourceContext,
+        ) -> CFRunLoopSourceRef;
+        pub fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+        pub fn CFRunLoopSourceSignal(source: CFRunLoopSourceRef);
+        pub fn CFRunLoopSourceInvalidate(source: CFRunLoopSourceRef);
+        pub fn CFRunLoopWakeUp(rl: CFRunLoopRef);
+        pub fn CFRunLoopRunInMode(mode: CFStringRef, seconds: f64, return_after_source_handled: u8) -> i32;
+        pub fn CFRunLoopStop(rl: CFRunLoopRef);
+        pub fn CFRelease(cf: *const c_void);
+    }
+}
+
+/// A version-0 `CFRunLoopSource` whose `perform` callback polls the runtime.
+///
+/// The source is signalled from [`Waker::wake`], which may be called from any
+/// thread; `CFRunLoopSourceSignal`/`CFRunLoopWakeUp` are documented by Apple
+/// as safe to call cross-thread, so this type is `Send + Sync` even though it
+/// wraps raw CoreFoundation pointers.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+struct RunLoopSource {
+    source: cf::CFRunLoopSourceRef,
+    run_loop: cf::CFRunLoopRef,
+    // The context's `info` pointer, `Box::into_raw`'d from a
+    // `Box<Box<dyn Fn()>>` so the closure has a stable heap address that
+    // outlives `new()` (unlike the address of a local variable); freed in
+    // `Drop` with the matching `Box::from_raw`.
+    perform: *mut Box<dyn Fn()>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+unsafe impl Send for RunLoopSource {}
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+unsafe impl Sync for RunLoopSource {}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl RunLoopSource {
+    /// Creates a run loop source, adds it to the main run loop in the
+    /// default mode, and arranges for `perform` to be invoked when the
+    /// source is signalled and the run loop next spins.
+    fn new(perform: impl Fn() + 'static) -> Self {
+        // Box the closure twice: the outer `Box::into_raw` gives the
+        // context's `info` pointer a stable heap address that survives
+        // `new()` returning, unlike the address of a local variable.
+        let perform: Box<Box<dyn Fn()>> = Box::new(Box::new(perform));
+        let perform = Box::into_raw(perform);
+        let info = perform as *mut core::ffi::c_void;
+
+        unsafe extern "C" fn perform_trampoline(info: *mut core::ffi::c_void) {
+            let perform = unsafe { &*(info as *const Box<dyn Fn()>) };
+            perform();
+        }
+
+        let mut context = cf::CFRunLoopSourceContext {
+            version: 0,
+            info,
+            retain: None,
+            release: None,
+            copy_description: None,
+            equal: None,
+            hash: None,
+            schedule: None,
+            cancel: None,
+            perform: Some(perform_trampoline),
+        };
+
+        unsafe {
+            let run_loop = cf::CFRunLoopGetMain();
+            let source = cf::CFRunLoopSourceCreate(core::ptr::null(), 0, &mut context);
+            cf::CFRunLoopAddSource(run_loop, source, cf::kCFRunLoopDefaultMode);
+            Self {
+                source,
+                run_loop,
+                perform,
+            }
+        }
+    }
+
+    /// Signals the source and wakes the run loop so the `perform` callback
+    /// runs as soon as possible, even if the run loop is currently blocked
+    /// waiting for input.
+    fn signal(&self) {
+        unsafe {
+            cf::CFRunLoopSourceSignal(self.source);
+            cf::CFRunLoopWakeUp(self.run_loop);
+        }
+    }
+
+    /// Stops the run loop this source is attached to, breaking it out of a
+    /// blocking `CFRunLoopRunInMode` call (used to implement [`LoopHandle::quit`]).
+    fn stop(&self) {
+        unsafe {
+            cf::CFRunLoopStop(self.run_loop);
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl Drop for RunLoopSource {
+    fn drop(&mut self) {
+        unsafe {
+            cf::CFRunLoopSourceInvalidate(self.source);
+            cf::CFRelease(self.source as *const _);
+            drop(Box::from_raw(self.perform));
+        }
+    }
+}
+
+/// Moves a reused, repeating `NSTimer`'s fire date to `deadline`, or pushes
+/// it out to the distant future when there is no pending work. The timer is
+/// never invalidated so it can be re-armed again later without recreating
+/// it.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn set_timer_fire_date(timer: *mut NSTimer, deadline: Option<Instant>) {
+    let fire_date = match deadline {
+        Some(deadline) => {
+            let secs = deadline.saturating_duration_since(Instant::now()).as_secs_f64();
+            unsafe { NSDate::dateWithTimeIntervalSinceNow(secs) }
+        }
+        None => unsafe { NSDate::distantFuture() },
+    };
+    let _: () = unsafe { msg_send![timer, setFireDate: &*fire_date] };
+}
+
+/// A handle that prompts the native event loop to poll the Compo runtime
+/// immediately, instead of waiting for the next deadline timer tick.
+///
+/// A `Waker` is cheap to clone and, unlike the runtime itself, may be sent to
+/// and invoked from any thread - this is the intended way to get a timely
+/// poll after waking a future from a worker thread (for example inside
+/// [`vm_exec`] on Android).
+#[derive(Clone)]
+pub struct Waker(WakerInner);
+
+#[derive(Clone)]
+enum WakerInner {
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    RunLoop(Arc<RunLoopSource>),
+    #[cfg(windows)]
+    Windows(u32),
+    #[cfg(target_os = "android")]
+    Android(Arc<GlobalRef>, jlong),
+    /// No native loop to interrupt (e.g. [`ManualPump`], or an
+    /// [`AndroidJniPump`] whose `Handler` hasn't been installed yet);
+    /// `wake()` is a no-op.
+    Manual,
+}
+
+impl Waker {
+    /// Wakes the native event loop so it polls the runtime as soon as it
+    /// next gets a chance to run, bypassing the deadline timer.
+    pub fn wake(&self) {
+        match &self.0 {
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            WakerInner::RunLoop(source) => source.signal(),
+            #[cfg(windows)]
+            WakerInner::Windows(thread_id) => unsafe {
+                let _ = PostThreadMessageW(
+                    *thread_id,
+                    WM_APP_POLL,
+                    Default::default(),
+                    Default::default(),
+                );
+            },
+            #[cfg(target_os = "android")]
+            WakerInner::Android(handler, handle) => {
+                let handle = *handle;
+                vm_exec(|mut env| {
+                    let runnable = match env.new_object(
+                        "rust/compo/MainLoop",
+                        "(J)V",
+                        &[JValue::Long(handle)],
+                    ) {
+                        Ok(obj) => obj,
+                        Err(e) => {
+                            error!(?e, "Can't create poll runnable.");
+                            return;
+                        }
+                    };
+                    if let Err(e) = env.call_method(
+                        handler.as_obj(),
+                        "post",
+                        "(Ljava/lang/Runnable;)Z",
+                        &[(&runnable).into()],
+                    ) {
+                        error!(?e, "Can't post poll runnable to the main Handler.");
+                    }
+                });
+            }
+            WakerInner::Manual => {}
+        }
+    }
+}
+
+thread_local! {
+    /// The [`LoopHandle`] of the [`Pump`] currently being driven on this
+    /// thread, registered by [`run_with`] (and the Android [`run`]) so
+    /// components can reach it through [`current_loop_handle`] without it
+    /// being threaded through every call.
+    static CURRENT_LOOP_HANDLE: RefCell<Option<LoopHandle>> = const { RefCell::new(None) };
+
+    /// The [`Waker`] of the [`Pump`] currently being driven on this thread,
+    /// registered alongside [`CURRENT_LOOP_HANDLE`] so components can reach
+    /// it through [`current_waker`] without it being threaded through every
+    /// call.
+    static CURRENT_WAKER: RefCell<Option<Waker>> = const { RefCell::new(None) };
+}
+
+/// Returns the [`Waker`] of the loop currently running on this thread, if
+/// any. Intended to be called from within a component's async task so a
+/// clone of the [`Waker`] can be handed to a worker thread, which can then
+/// call [`Waker::wake`] to have a future it completes (e.g. via `vm_exec`)
+/// picked up promptly instead of waiting for the next scheduled deadline.
+pub fn current_waker() -> Option<Waker> {
+    CURRENT_WAKER.with_borrow(Clone::clone)
+}
+
+/// Returns the [`LoopHandle`] of the loop currently running on this thread,
+/// if any. Intended to be called from within a component's async task, which
+/// runs on the same thread as the [`run_with`] loop (or, on Android, the
+/// thread that called `run`) that is driving it.
+pub fn current_loop_handle() -> Option<LoopHandle> {
+    CURRENT_LOOP_HANDLE.with_borrow(Clone::clone)
+}
+
+/// A clonable handle that asks a running loop to stop, waking it immediately
+/// if it is currently idle.
+///
+/// Obtained from within a running component via [`current_loop_handle`], or
+/// from a concrete [`Pump`] via [`Pump::handle`] before calling [`run_with`].
+/// `quit()` does the right native thing per platform: `PostThreadMessageW`
+/// with `WM_QUIT` on Windows, `CFRunLoopStop` (and, under the `application`
+/// feature, `-[NSApplication stop:]`) on macOS/iOS, and a Java
+/// `MainLoop.finish()` call on Android.
+#[derive(Clone)]
+pub struct LoopHandle(Arc<dyn LoopHandleTarget>);
+
+/// The per-[`Pump`] implementation behind a [`LoopHandle`]; kept private
+/// since the only thing a caller can do with a `LoopHandle` is `quit()` it.
+trait LoopHandleTarget: Send + Sync {
+    fn quit(&self);
+}
+
+impl LoopHandle {
+    fn new(target: Arc<dyn LoopHandleTarget>) -> Self {
+        Self(target)
+    }
+
+    /// Asks the loop this handle was obtained from to stop as soon as
+    /// possible, waking it immediately if it is currently idle.
+    pub fn quit(&self) {
+        self.0.quit();
+    }
+}
+
+/// Custom message posted by [`Waker::wake`] on Windows to prompt an
+/// immediate poll of the runtime; recognised and consumed by [`Win32Pump`].
+#[cfg(windows)]
+const WM_APP_POLL: u32 = WM_APP;
+
+/// The default [`Pump`] on Windows: a self-contained Win32 message loop built
+/// on `PeekMessage`/`MsgWaitForMultipleObjectsEx`, replacing the previous
+/// fixed-interval `handle_windows_message` polling.
+#[cfg(windows)]
+pub struct Win32Pump {
+    thread_id: u32,
+    waker: Waker,
+    poll: Option<PollFn>,
+    deadline: Option<Instant>,
+    quit: Arc<AtomicBool>,
+}
+
+#[cfg(windows)]
+impl Win32Pump {
+    /// Creates a pump bound to the calling thread, which must be the thread
+    /// [`run_with`] (or [`run`]) is called from and stays on for the
+    /// lifetime of the loop.
+    pub fn new() -> Self {
+        let thread_id = unsafe { GetCurrentThreadId() };
+        Self {
+            thread_id,
+            waker: Waker(WakerInner::Windows(thread_id)),
+            poll: None,
+            deadline: None,
+            quit: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// The [`LoopHandle`] target for [`Win32Pump`]: sets the shared quit flag and
+/// posts `WM_QUIT` to the pump's thread so a blocked
+/// `MsgWaitForMultipleObjectsEx` wakes up immediately.
+#[cfg(windows)]
+struct Win32QuitTarget {
+    thread_id: u32,
+    quit: Arc<AtomicBool>,
+}
+
+#[cfg(windows)]
+impl LoopHandleTarget for Win32QuitTarget {
+    fn quit(&self) {
+        self.quit.store(true, Ordering::Release);
+        unsafe {
+            let _ = PostThreadMessageW(self.thread_id, WM_QUIT, Default::default(), Default::default());
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Default for Win32Pump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(windows)]
-fn handle_windows_message(r#loop: &Loop) {
-    // Use PeekMessage instead of GetMessage because GetMessage blocks until a message is available
-    unsafe {
-        let mut msg = MSG::default();
-        // Check if there are messages in the queue without blocking
-        while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
-            // If it's a WM_QUIT message, exit the loop
-            if msg.message == WM_QUIT {
-                r#loop.quit();
-                break;
+impl Pump for Win32Pump {
+    fn bind(&mut self, mut poll: PollFn) {
+        self.deadline = poll();
+        self.poll = Some(poll);
+    }
+
+    fn wait_for_work(&mut self) {
+        let timeout_ms = match self.deadline {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .as_millis()
+                .min(u32::MAX as u128) as u32,
+            None => INFINITE,
+        };
+        unsafe {
+            let _ = MsgWaitForMultipleObjectsEx(
+                &[],
+                timeout_ms,
+                QS_ALLINPUT,
+                MWMO_INPUTAVAILABLE,
+            );
+        }
+    }
+
+    fn process_native_events(&mut self) {
+        // `wait_for_work` can return because the deadline timeout elapsed
+        // rather than because a message was posted - there is no `WM_TIMER`
+        // to catch that case, so re-poll unconditionally whenever the
+        // deadline has passed. Harmless if it hasn't: `poll_all` is a no-op
+        // when nothing is ready.
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            if let Some(poll) = &mut self.poll {
+                self.deadline = poll();
+            }
+        }
+
+        unsafe {
+            let mut msg = MSG::default();
+            while PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+                if msg.message == WM_QUIT {
+                    self.quit.store(true, Ordering::Release);
+                    break;
+                }
+
+                // A waker-posted ping: poll the runtime and recompute the
+                // next deadline right away.
+                if msg.message == WM_APP_POLL {
+                    if let Some(poll) = &mut self.poll {
+                        self.deadline = poll();
+                    }
+                    continue;
+                }
+
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+
+    fn wake(&self) {
+        self.waker.wake();
+    }
+
+    fn should_quit(&self) -> bool {
+        self.quit.load(Ordering::Acquire)
+    }
+
+    fn handle(&self) -> LoopHandle {
+        LoopHandle::new(Arc::new(Win32QuitTarget {
+            thread_id: self.thread_id,
+            quit: self.quit.clone(),
+        }))
+    }
+
+    fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+}
+
+/// The default [`Pump`] on iOS (and the one [`AppKitPump`] wraps on macOS): a
+/// run-loop-source-driven pump built on `CFRunLoopRunInMode`, replacing the
+/// previous fixed-interval `NSTimer` polling.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub struct RunLoopPump {
+    source: Arc<RunLoopSource>,
+    waker: Waker,
+    timer: *mut NSTimer,
+    poll: Rc<RefCell<Option<PollFn>>>,
+    deadline: Rc<Cell<Option<Instant>>>,
+    quit: Arc<AtomicBool>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl RunLoopPump {
+    /// Creates a pump bound to the main run loop, which must be called from
+    /// the main thread.
+    pub fn new() -> Self {
+        let poll: Rc<RefCell<Option<PollFn>>> = Rc::new(RefCell::new(None));
+        let deadline: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+        let timer_cell: Rc<Cell<*mut NSTimer>> = Rc::new(Cell::new(core::ptr::null_mut()));
+
+        // 每次被唤醒或定时器到期时都重新轮询一次，并根据返回的截止时间
+        // 重新安排下一次触发。
+        let run_once = {
+            let poll = poll.clone();
+            let deadline = deadline.clone();
+            let timer_cell = timer_cell.clone();
+            move || {
+                let next = poll.borrow_mut().as_mut().and_then(|poll| poll());
+                deadline.set(next);
+                set_timer_fire_date(timer_cell.get(), next);
+            }
+        };
+
+        let source = Arc::new(RunLoopSource::new(run_once.clone()));
+        let waker = Waker(WakerInner::RunLoop(source.clone()));
+
+        let run_loop = NSRunLoop::mainRunLoop();
+        let poll_block = RcBlock::new(run_once);
+        let timer: *mut NSTimer = unsafe {
+            msg_send![NSTimer::class(),
+                scheduledTimerWithTimeInterval: 86400.0,
+                repeats: true,
+                block: &*poll_block
+            ]
+        };
+        timer_cell.set(timer);
+        let mode = NSString::from_str("NSDefaultRunLoopMode");
+        let _: () = unsafe { msg_send![&run_loop, addTimer: timer, forMode: &*mode] };
+
+        Self {
+            source,
+            waker,
+            timer,
+            poll,
+            deadline,
+            quit: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// The [`LoopHandle`] target for [`RunLoopPump`] (and, via composition,
+/// [`AppKitPump`]): sets the shared quit flag and stops the run loop so a
+/// blocked `CFRunLoopRunInMode` call returns immediately.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+struct RunLoopQuitTarget {
+    source: Arc<RunLoopSource>,
+    quit: Arc<AtomicBool>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl LoopHandleTarget for RunLoopQuitTarget {
+    fn quit(&self) {
+        self.quit.store(true, Ordering::Release);
+        self.source.stop();
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl Default for RunLoopPump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+impl Pump for RunLoopPump {
+    fn bind(&mut self, mut poll: PollFn) {
+        let next = poll();
+        self.deadline.set(next);
+        set_timer_fire_date(self.timer, next);
+        *self.poll.borrow_mut() = Some(poll);
+    }
+
+    fn wait_for_work(&mut self) {
+        let timeout = match self.deadline.get() {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()).as_secs_f64(),
+            // CFRunLoopRunInMode treats a very large timeout as "block until
+            // a source or timer fires".
+            None => 1.0e10,
+        };
+        unsafe {
+            cf::CFRunLoopRunInMode(cf::kCFRunLoopDefaultMode, timeout, 1);
+        }
+    }
+
+    fn process_native_events(&mut self) {
+        // `wait_for_work` already ran the loop far enough to dispatch one
+        // source or timer; drain anything left queued without blocking.
+        unsafe {
+            cf::CFRunLoopRunInMode(cf::kCFRunLoopDefaultMode, 0.0, 1);
+        }
+    }
+
+    fn wake(&self) {
+        self.waker.wake();
+    }
+
+    fn should_quit(&self) -> bool {
+        self.quit.load(Ordering::Acquire)
+    }
+
+    fn handle(&self) -> LoopHandle {
+        LoopHandle::new(Arc::new(RunLoopQuitTarget {
+            source: self.source.clone(),
+            quit: self.quit.clone(),
+        }))
+    }
+
+    fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+}
+
+/// The default [`Pump`] on macOS: a [`RunLoopPump`] plus (behind the
+/// `application` feature) an activated `NSApplication`.
+#[cfg(target_os = "macos")]
+pub struct AppKitPump(RunLoopPump);
+
+#[cfg(target_os = "macos")]
+impl AppKitPump {
+    pub fn new() -> Self {
+        #[cfg(feature = "application")]
+        if let Some(mtm) = objc2::MainThreadMarker::new() {
+            objc2_app_kit::NSApplication::sharedApplication(mtm).activate();
+        } else {
+            tracing::error!("Can't run on non-main-thread.")
+        }
+        Self(RunLoopPump::new())
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Default for AppKitPump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Pump for AppKitPump {
+    fn bind(&mut self, poll: PollFn) {
+        self.0.bind(poll);
+    }
+
+    fn wait_for_work(&mut self) {
+        self.0.wait_for_work();
+    }
+
+    fn process_native_events(&mut self) {
+        self.0.process_native_events();
+    }
+
+    fn wake(&self) {
+        self.0.wake();
+    }
+
+    fn should_quit(&self) -> bool {
+        self.0.should_quit()
+    }
+
+    fn handle(&self) -> LoopHandle {
+        LoopHandle::new(Arc::new(AppKitQuitTarget(self.0.handle())))
+    }
+
+    fn waker(&self) -> Waker {
+        self.0.waker()
+    }
+}
+
+/// The [`LoopHandle`] target for [`AppKitPump`]: stops the wrapped
+/// [`RunLoopPump`] and, under the `application` feature, also asks the
+/// activated `NSApplication` to stop.
+#[cfg(target_os = "macos")]
+struct AppKitQuitTarget(LoopHandle);
+
+#[cfg(target_os = "macos")]
+impl LoopHandleTarget for AppKitQuitTarget {
+    fn quit(&self) {
+        self.0.quit();
+        #[cfg(feature = "application")]
+        if let Some(mtm) = objc2::MainThreadMarker::new() {
+            objc2_app_kit::NSApplication::sharedApplication(mtm).stop(None);
+        }
+    }
+}
+
+/// The default [`Pump`] on Android: dispatch is entirely push-driven by the
+/// JVM calling the registered `poll_all` native method, so
+/// `wait_for_work`/`process_native_events` have nothing to do - they exist
+/// only so `AndroidJniPump` can be driven through the same [`Pump`]
+/// interface as the other platforms when embedding Compo in a host app.
+#[cfg(target_os = "android")]
+pub struct AndroidJniPump {
+    poll: Option<PollFn>,
+    waker: Option<Waker>,
+    // The `jlong` handle of the `AndroidRuntimeState` this pump lives in, so
+    // `wake()`/`handle()` can address this pump's own activity/runtime
+    // rather than assuming there's only ever one. Set once `run` has boxed
+    // the state and learned its own address.
+    handle: jlong,
+}
+
+#[cfg(target_os = "android")]
+impl AndroidJniPump {
+    pub fn new() -> Self {
+        Self {
+            poll: None,
+            waker: None,
+            handle: 0,
+        }
+    }
+
+    /// Records the `jlong` handle Java was given for the
+    /// `AndroidRuntimeState` this pump lives in; set once `run` has boxed
+    /// the state.
+    fn set_handle(&mut self, handle: jlong) {
+        self.handle = handle;
+    }
+
+    /// Installs the [`Waker`] used to post a poll request to the main
+    /// `Handler`; set once `run` has created it.
+    fn set_waker(&mut self, waker: Waker) {
+        self.waker = Some(waker);
+    }
+
+    /// Invoked by the native `poll_all` method registered on `MainLoop` to
+    /// advance the runtime once.
+    fn poll_once(&mut self) {
+        if let Some(poll) = &mut self.poll {
+            poll();
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+impl Default for AndroidJniPump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "android")]
+impl Pump for AndroidJniPump {
+    fn bind(&mut self, poll: PollFn) {
+        self.poll = Some(poll);
+    }
+
+    fn wait_for_work(&mut self) {
+        // Nothing to wait for: Java's main Looper owns blocking and calls
+        // back into `poll_all` whenever it has dispatched a message.
+    }
+
+    fn process_native_events(&mut self) {
+        // Events are dispatched by Java before `poll_all` is invoked; there
+        // is nothing left to pump from the Rust side.
+    }
+
+    fn wake(&self) {
+        if let Some(waker) = &self.waker {
+            waker.wake();
+        }
+    }
+
+    fn handle(&self) -> LoopHandle {
+        LoopHandle::new(Arc::new(AndroidQuitTarget {
+            handle: self.handle,
+        }))
+    }
+
+    fn waker(&self) -> Waker {
+        self.waker
+            .clone()
+            .unwrap_or_else(|| Waker(WakerInner::Manual))
+    }
+}
+
+/// The [`LoopHandle`] target for [`AndroidJniPump`]: asks Java to finish the
+/// hosting activity that owns `handle`, which will eventually drive the
+/// `on_destroy` native and tear that runtime down.
+#[cfg(target_os = "android")]
+struct AndroidQuitTarget {
+    handle: jlong,
+}
+
+#[cfg(target_os = "android")]
+impl LoopHandleTarget for AndroidQuitTarget {
+    fn quit(&self) {
+        let handle = self.handle;
+        vm_exec(|mut env| {
+            if let Err(e) = env.call_static_method(
+                "rust/compo/MainLoop",
+                "finish",
+                "(J)V",
+                &[JValue::Long(handle)],
+            ) {
+                error!(?e, "Finish failed.");
             }
+        });
+    }
+}
+
+/// A trivial [`Pump`] with no native loop at all, intended for
+/// deterministically stepping the runtime from tests: call
+/// [`ManualPump::step`] as many times as the test needs instead of blocking
+/// in [`run_with`].
+pub struct ManualPump {
+    poll: Option<PollFn>,
+    waker: Waker,
+    quit: Arc<AtomicBool>,
+}
 
-            // Translate virtual key messages
-            let _ = TranslateMessage(&msg);
-            // Dispatch message to window procedure
-            DispatchMessageW(&msg);
+impl Default for ManualPump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualPump {
+    pub fn new() -> Self {
+        Self {
+            poll: None,
+            waker: Waker(WakerInner::Manual),
+            quit: Arc::new(AtomicBool::new(false)),
         }
     }
+
+    /// Polls the runtime once and returns its next scheduled deadline, if
+    /// any. Panics if called before the pump has been [`Pump::bind`]-ed.
+    pub fn step(&mut self) -> Option<Instant> {
+        self.poll.as_mut().expect("ManualPump not bound yet")()
+    }
+
+    /// Sets the quit flag, so [`Pump::should_quit`] returns `true` from now
+    /// on (the same flag a [`LoopHandle`] from [`Pump::handle`] sets).
+    pub fn quit(&mut self) {
+        self.quit.store(true, Ordering::Release);
+    }
 }
 
+/// The [`LoopHandle`] target for [`ManualPump`]: just flips the shared quit
+/// flag, since there is no native wait to interrupt.
+struct ManualQuitTarget(Arc<AtomicBool>);
+
+impl LoopHandleTarget for ManualQuitTarget {
+    fn quit(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+impl Pump for ManualPump {
+    fn bind(&mut self, poll: PollFn) {
+        self.poll = Some(poll);
+    }
+
+    fn wait_for_work(&mut self) {
+        // Never blocks: the test harness decides when to call `step`.
+    }
+
+    fn process_native_events(&mut self) {
+        // There is no native loop to dispatch; tests call `step` directly.
+    }
+
+    fn wake(&self) {
+        // No-op: there is no blocked native wait to interrupt.
+    }
+
+    fn should_quit(&self) -> bool {
+        self.quit.load(Ordering::Acquire)
+    }
+
+    fn handle(&self) -> LoopHandle {
+        LoopHandle::new(Arc::new(ManualQuitTarget(self.quit.clone())))
+    }
+
+    fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+}
+
+/// An alias for [`ManualPump`] used where a pump is needed purely to drive
+/// the runtime without any UI, such as headless integration tests or
+/// server-side rendering of components.
+pub type HeadlessPump = ManualPump;
+
 /// Runs the platform-specific event loop with the given entry component.
 ///
 /// This function initializes and starts the appropriate event loop for the current platform:
-/// - **Windows**: Uses Win32 message loop with PeekMessage for non-blocking message processing
-/// - **macOS**: Uses NSApplication with NSRunLoop and NSTimer for periodic runtime polling
-/// - **iOS**: Uses NSRunLoop with NSTimer for periodic runtime polling (without NSApplication)
+/// - **Windows**: Uses [`Win32Pump`], a Win32 message loop with non-blocking message processing
+/// - **macOS**: Uses [`AppKitPump`], an `NSApplication` plus a waker-driven `CFRunLoopSource`
+/// - **iOS**: Uses [`RunLoopPump`], a waker-driven `CFRunLoopSource` (suitable for iOS apps)
 ///
 /// The function creates a Compo runtime, spawns the entry component as an async task,
 /// and integrates with the platform's native event loop to ensure proper execution
-/// of async components.
+/// of async components. This is a thin wrapper around [`run_with`] using each
+/// platform's default [`Pump`]; call `run_with` directly to supply a custom one.
 ///
 /// # Type Parameters
 /// * `C` - The component type that implements `Component<'a>`
@@ -108,27 +1149,8 @@ fn handle_windows_message(r#loop: &Loop) {
 /// # Arguments
 /// * `entry` - The entry point async function that will be executed as the root component
 ///
-/// # Platform-specific behavior
-/// - **Windows**: Registers a message handler and runs the Loop with Windows message processing
-/// - **macOS**: Creates NSApplication, sets up a timer for runtime polling, and runs the app loop
-/// - **iOS**: Sets up NSRunLoop with a timer for runtime polling (suitable for iOS apps)
-///
 /// # Examples
 /// ```rust
-/// //! Platform-specific event loop implementations for the Compo framework.
-/// //!
-/// //! This module provides cross-platform event loop integration that allows Compo
-/// //! applications to run natively on different operating systems. Each platform
-/// //! uses its native event loop mechanism:
-/// //!
-/// //! - **Windows**: Win32 message loop with PeekMessage for non-blocking processing
-/// //! - **macOS**: NSApplication with NSRunLoop and NSTimer for periodic polling
-/// //! - **iOS**: NSRunLoop with NSTimer for periodic polling (without NSApplication)
-/// //! - **Android**: JNI integration with Java MainLoop for Android event system
-/// //!
-/// //! The module exports platform-appropriate `run` functions that initialize the
-/// //! Compo runtime and integrate it with the platform's native event loop.
-///
 /// use compo::prelude::*;
 /// use compo_platform_loop::prelude::run;
 ///
@@ -148,115 +1170,31 @@ where
     F: AsyncFn(Weak<C>) + 'a,
 {
     #[cfg(windows)]
-    Loop::new()
-        .register_poll_handler(handle_windows_message)
-        .run(entry);
+    run_with(Win32Pump::new(), entry);
 
     #[cfg(target_os = "ios")]
     {
-        // 创建运行时和组件
-        let rt = Rc::new(Runtime::new());
-        let rt_weak = Rc::downgrade(&rt);
-        let c = Rc::new(C::new(rt_weak.clone()));
-        let c_weak = Rc::downgrade(&c);
-
-        // 启动异步任务
-        rt.spawn(async move { entry(c_weak).await });
-
-        // 获取主线程的运行循环
-        let run_loop = NSRunLoop::mainRunLoop();
-
-        // 创建一个定时器，用于定期轮询 Runtime
-        let poll_block = RcBlock::new(move || {
-            // 轮询 Runtime 以推进异步任务
-            if let Some(rt) = rt_weak.upgrade() {
-                rt.poll_all();
-            }
-        });
-
-        // 创建一个重复的定时器，每 0.01 秒轮询一次
-        let timer: *mut NSTimer = unsafe {
-            msg_send![NSTimer::class(),
-                scheduledTimerWithTimeInterval: 0.01,
-                repeats: true,
-                block: &*poll_block
-            ]
-        };
-
-        // 将定时器添加到运行循环中
-        let mode = NSString::from_str("NSDefaultRunLoopMode");
-        let _: () = unsafe { msg_send![&run_loop, addTimer: timer, forMode: &*mode] };
-
-        // 运行主循环，这会阻塞当前线程
-        #[cfg(not(feature = "application"))]
-        run_loop.run();
-        #[cfg(feature = "application")]
-        if let Some(mtm) = objc2::MainThreadMarker::new() {
-            objc2_ui_kit::UIApplication::main(None, None, mtm)
-        } else {
-            tracing::error!("Can't run on non-main-thread.")
-        }
+        run_with(RunLoopPump::new(), entry);
     }
 
     #[cfg(target_os = "macos")]
     {
-        // 创建运行时和组件
-        let rt = Rc::new(Runtime::new());
-        let rt_weak = Rc::downgrade(&rt);
-        let c = Rc::new(C::new(rt_weak.clone()));
-        let c_weak = Rc::downgrade(&c);
-
-        // 启动异步任务
-        rt.spawn(async move { entry(c_weak).await });
-
-        // 获取主线程的运行循环
-        let run_loop = NSRunLoop::mainRunLoop();
-
-        // 创建一个定时器，用于定期轮询 Runtime
-        let poll_block = RcBlock::new(move || {
-            // 轮询 Runtime 以推进异步任务
-            if let Some(rt) = rt_weak.upgrade() {
-                rt.poll_all();
-            }
-        });
-
-        // 创建一个重复的定时器，每 0.01 秒轮询一次
-        let timer: *mut NSTimer = unsafe {
-            msg_send![NSTimer::class(),
-                scheduledTimerWithTimeInterval: 0.01,
-                repeats: true,
-                block: &*poll_block
-            ]
-        };
-
-        // 将定时器添加到运行循环中
-        // 创建 NSDefaultRunLoopMode 字符串
-        let mode = NSString::from_str("NSDefaultRunLoopMode");
-        let _: () = unsafe { msg_send![&run_loop, addTimer: timer, forMode: &*mode] };
-
-        #[cfg(not(feature = "application"))]
-        run_loop.run();
-        #[cfg(feature = "application")]
-        if let Some(mtm) = objc2::MainThreadMarker::new() {
-            let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
-            app.activate();
-
-            // 运行应用程序主循环，这会阻塞当前线程
-            app.run();
-        } else {
-            tracing::error!("Can't run on non-main-thread.")
-        }
+        run_with(AppKitPump::new(), entry);
     }
 }
 
 /// Runs the Android-specific event loop with JNI integration.
 ///
 /// This function sets up the event loop for Android applications using JNI (Java Native Interface).
-/// It creates a Compo runtime in thread-local storage, spawns the entry component, and integrates
-/// with the Android Java MainLoop class for proper event loop execution.
+/// It boxes a fresh Compo runtime and component into a per-activity [`AndroidRuntimeState`] and
+/// integrates with the Android Java `MainLoop` class for proper event loop execution through an
+/// [`AndroidJniPump`]. The state is handed to Java as an opaque `jlong` handle (passed to
+/// `MainLoop.run`) that Java passes back as the first argument to every native callback, which
+/// lets multiple activities - each with its own `run` call, possibly on different threads - coexist
+/// in the same process.
 ///
-/// The function registers a native method `poll_all` that can be called from Java to advance
-/// the async runtime, enabling proper integration with Android's event system.
+/// The function registers native methods (`poll_all` plus the lifecycle callbacks) that Java calls
+/// to advance the async runtime and notify it of activity lifecycle changes.
 ///
 /// # Type Parameters
 /// * `C` - The component type that implements `Component<'static>` (must be 'static for Android)
@@ -268,27 +1206,13 @@ where
 ///
 /// # Android Integration
 /// This function:
-/// 1. Creates a thread-local Compo runtime and component
+/// 1. Creates a Compo runtime and component, boxed into a per-activity handle
 /// 2. Spawns the entry component as an async task
-/// 3. Calls the Java `MainLoop.run()` method to start the Android event loop
-/// 4. Registers the native `poll_all` method for runtime advancement
+/// 3. Calls the Java `MainLoop.run(J)V` method, passing the handle, to start the Android event loop
+/// 4. Registers the native `poll_all` and lifecycle methods for runtime advancement
 ///
 /// # Examples
 /// ```rust
-/// //! Platform-specific event loop implementations for the Compo framework.
-/// //!
-/// //! This module provides cross-platform event loop integration that allows Compo
-/// //! applications to run natively on different operating systems. Each platform
-/// //! uses its native event loop mechanism:
-/// //!
-/// //! - **Windows**: Win32 message loop with PeekMessage for non-blocking processing
-/// //! - **macOS**: NSApplication with NSRunLoop and NSTimer for periodic polling
-/// //! - **iOS**: NSRunLoop with NSTimer for periodic polling (without NSApplication)
-/// //! - **Android**: JNI integration with Java MainLoop for Android event system
-/// //!
-/// //! The module exports platform-appropriate `run` functions that initialize the
-/// //! Compo runtime and integrate it with the platform's native event loop.
-///
 /// use compo::prelude::*;
 /// use compo_platform_loop::prelude::run;
 /// use jni::JavaVM;
@@ -308,29 +1232,108 @@ where
     C: Component<'static> + 'static,
     F: AsyncFn(Weak<C>) + 'static,
 {
-    JAVA_VM.set(Ok(vm));
-    RT.with(|rt| {
-        let rt_weak = Rc::downgrade(rt);
-        let c = Rc::new(C::new(rt_weak.clone()));
-        let c_weak = Rc::downgrade(&c);
-
-        // 启动异步任务
-        rt.spawn(async move { entry(c_weak).await });
-        COMPONENT.set(c);
-    });
+    if JAVA_VM.set(vm).is_err() {
+        error!("`run` called again on a process that already cached a JavaVM; reusing the cached one.");
+    }
+
+    let rt = Rc::new(Runtime::new());
+    let rt_weak = Rc::downgrade(&rt);
+    let c = Rc::new(C::new(rt_weak.clone()));
+    let c_weak = Rc::downgrade(&c);
+
+    // 启动异步任务
+    rt.spawn(async move { entry(c_weak).await });
+
+    let state = Box::into_raw(Box::new(AndroidRuntimeState {
+        rt,
+        component: c,
+        pump: AndroidJniPump::new(),
+        listeners: Vec::new(),
+    }));
+    let handle = state as jlong;
+    unsafe { state_from_handle(handle) }.pump.set_handle(handle);
+    CURRENT_HANDLE.set(handle);
+    // Set before `bind`, mirroring `run_with`: even though `AndroidJniPump`'s
+    // own `bind` is push-driven rather than synchronous, the first
+    // `poll_all` call from Java can race `vm_exec` below, so these must
+    // already be populated before anything can start polling.
+    CURRENT_LOOP_HANDLE.set(Some(unsafe { state_from_handle(handle) }.pump.handle()));
+    CURRENT_WAKER.set(Some(unsafe { state_from_handle(handle) }.pump.waker()));
+    unsafe { state_from_handle(handle) }.pump.bind(Box::new(move || {
+        let rt = rt_weak.upgrade()?;
+        rt.poll_all();
+        rt.next_deadline()
+    }));
+
     vm_exec(|mut env| {
         const CLASS: &str = "rust/compo/MainLoop";
-        if let Err(e) = env.call_static_method(CLASS, "run", "()V", &[]) {
+        if let Err(e) = env.call_static_method(CLASS, "run", "(J)V", &[JValue::Long(handle)]) {
             error!(?e, "Run failed.");
         }
-        let method = NativeMethod {
-            name: "poll_all".into(),
-            sig: "()V".into(),
-            fn_ptr: poll_all as *mut _,
-        };
-        if let Err(e) = env.register_native_methods(CLASS, &[method]) {
+        let methods = [
+            NativeMethod {
+                name: "poll_all".into(),
+                sig: "(J)V".into(),
+                fn_ptr: poll_all as *mut _,
+            },
+            NativeMethod {
+                name: "on_create".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_create as *mut _,
+            },
+            NativeMethod {
+                name: "on_resume".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_resume as *mut _,
+            },
+            NativeMethod {
+                name: "on_pause".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_pause as *mut _,
+            },
+            NativeMethod {
+                name: "on_stop".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_stop as *mut _,
+            },
+            NativeMethod {
+                name: "on_destroy".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_destroy as *mut _,
+            },
+            NativeMethod {
+                name: "on_low_memory".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_low_memory as *mut _,
+            },
+            NativeMethod {
+                name: "on_save_instance_state".into(),
+                sig: "(J)V".into(),
+                fn_ptr: on_save_instance_state as *mut _,
+            },
+        ];
+        if let Err(e) = env.register_native_methods(CLASS, &methods) {
             error!(?e, "Register native method failed.");
         }
+
+        // 创建一个绑定到主 Looper 的 Handler，供 `Waker` 在其他线程上
+        // post 一个 Runnable 来立即触发一次轮询。
+        match env
+            .find_class("android/os/Looper")
+            .and_then(|looper_class| {
+                env.call_static_method(looper_class, "getMainLooper", "()Landroid/os/Looper;", &[])
+            })
+            .and_then(|looper| looper.l())
+            .and_then(|looper| env.new_object("android/os/Handler", "(Landroid/os/Looper;)V", &[(&looper).into()]))
+            .and_then(|handler| env.new_global_ref(handler))
+        {
+            Ok(handler) => {
+                let waker = Waker(WakerInner::Android(Arc::new(handler), handle));
+                unsafe { state_from_handle(handle) }.pump.set_waker(waker.clone());
+                CURRENT_WAKER.set(Some(waker));
+            }
+            Err(e) => error!(?e, "Can't create main Handler for waker."),
+        }
     });
 }
 
@@ -338,25 +1341,110 @@ where
 ///
 /// This function is registered as a JNI native method and called by the Android
 /// Java MainLoop to poll and advance all pending async tasks in the Compo runtime.
-/// It accesses the thread-local runtime and calls `poll_all()` to process any
-/// ready futures.
+/// It forwards into the poll function bound by [`AndroidJniPump::bind`].
 ///
 /// # Safety
 /// This function is marked as `unsafe` because it's a C-style callback function
-/// that will be called from Java via JNI. The JNI environment parameter is
-/// currently unused but required by the JNI interface.
+/// that will be called from Java via JNI. `handle` must be a pointer previously
+/// returned by [`run`] and not yet destroyed by [`on_destroy`].
 ///
 /// # Arguments
 /// * `_env` - The JNI environment (unused in current implementation)
+/// * `handle` - The `AndroidRuntimeState` pointer Java was given by [`run`]
+#[cfg(target_os = "android")]
+unsafe extern "C" fn poll_all(_env: JNIEnv, handle: jlong) {
+    unsafe { state_from_handle(handle) }.pump.poll_once();
+}
+
+/// Native method called from Java when `Activity.onCreate` fires.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by [`on_destroy`].
+#[cfg(target_os = "android")]
+unsafe extern "C" fn on_create(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::Create);
+}
+
+/// Native method called from Java when `Activity.onResume` fires.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by [`on_destroy`].
+#[cfg(target_os = "android")]
+unsafe extern "C" fn on_resume(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::Resume);
+}
+
+/// Native method called from Java when `Activity.onPause` fires.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by [`on_destroy`].
+#[cfg(target_os = "android")]
+unsafe extern "C" fn on_pause(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::Pause);
+}
+
+/// Native method called from Java when `Activity.onStop` fires.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by [`on_destroy`].
+#[cfg(target_os = "android")]
+unsafe extern "C" fn on_stop(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::Stop);
+}
+
+/// Native method called from Java when `Activity.onDestroy` fires.
+///
+/// Dispatches [`LifecycleEvent::Destroy`] so components can react, then tears
+/// the runtime down: `handle` is reclaimed into a `Box` and dropped, which
+/// releases the pump, the root component, and the listener list together.
+/// [`Waker`] clones handed out earlier are not invalidated and still post
+/// `handle` when woken. `handle` must not be used again after this call.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by a prior call to this function.
+#[cfg(target_os = "android")]
+unsafe extern "C" fn on_destroy(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::Destroy);
+    if CURRENT_HANDLE.get() == handle {
+        CURRENT_HANDLE.set(0);
+        CURRENT_LOOP_HANDLE.set(None);
+        CURRENT_WAKER.set(None);
+    }
+    drop(unsafe { Box::from_raw(handle as *mut AndroidRuntimeState) });
+}
+
+/// Native method called from Java on `Application.onLowMemory`/`onTrimMemory`.
+///
+/// Gives components a chance to release caches before the OS starts killing
+/// background processes.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by [`on_destroy`].
 #[cfg(target_os = "android")]
-unsafe extern "C" fn poll_all(_env: JNIEnv) {
-    RT.with(|rt| rt.poll_all());
+unsafe extern "C" fn on_low_memory(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::LowMemory);
+}
+
+/// Native method called from Java when `Activity.onSaveInstanceState` fires.
+///
+/// # Safety
+/// `handle` must be a pointer previously returned by [`run`] and not yet
+/// destroyed by [`on_destroy`].
+#[cfg(target_os = "android")]
+unsafe extern "C" fn on_save_instance_state(_env: JNIEnv, handle: jlong) {
+    dispatch_lifecycle_event(unsafe { state_from_handle(handle) }, LifecycleEvent::SaveInstanceState);
 }
 
 /// Executes a closure with an attached JNI environment.
 ///
 /// This function provides thread-safe access to the Java VM environment by:
-/// 1. Borrowing the stored JavaVM instance
+/// 1. Borrowing the cached, process-wide `JavaVM`
 /// 2. Attaching the current thread to the JVM
 /// 3. Executing the provided closure with the attached environment
 ///
@@ -376,11 +1464,54 @@ pub fn vm_exec<F>(f: F)
 where
     F: for<'a> FnOnce(AttachGuard<'a>),
 {
-    JAVA_VM.with_borrow_mut(move |vm| match vm {
-        Ok(vm) => match vm.attach_current_thread() {
+    match JAVA_VM.get() {
+        Some(vm) => match vm.attach_current_thread() {
             Ok(env) => f(env),
             Err(e) => error!(?e, "Can't attach current thread."),
         },
-        Err(e) => error!(?e, "Java VM is not initialized, please call the `run` function and set the correct JavaVM first."),
-    })
+        None => error!("Java VM is not initialized, please call the `run` function and set the correct JavaVM first."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// Binds a [`ManualPump`] directly, bypassing [`run_with`], so the
+    /// stepping and `quit()` bookkeeping can be driven deterministically.
+    #[test]
+    fn manual_pump_steps_and_quits() {
+        let polls = Rc::new(Cell::new(0));
+        let mut pump = ManualPump::new();
+        pump.bind(Box::new({
+            let polls = polls.clone();
+            move || {
+                polls.set(polls.get() + 1);
+                None
+            }
+        }));
+
+        assert_eq!(pump.step(), None);
+        assert_eq!(polls.get(), 1);
+        assert_eq!(pump.step(), None);
+        assert_eq!(polls.get(), 2);
+
+        assert!(!pump.should_quit());
+        pump.quit();
+        assert!(pump.should_quit());
+    }
+
+    #[test]
+    fn loop_handle_quit_flips_should_quit() {
+        let mut pump = ManualPump::new();
+        pump.bind(Box::new(|| None));
+
+        let handle = pump.handle();
+        assert!(!pump.should_quit());
+        handle.quit();
+        assert!(pump.should_quit());
+    }
 }